@@ -0,0 +1,45 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The [Channel] trait a [crate::router::Router] dispatches inbound messages to and pulls
+//! outbound messages from, one implementor per assigned ANT channel.
+
+#[cfg(feature = "std")]
+use std::boxed::Box;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::messages::{AntMessage, TransmitableMessage};
+
+/// Where a [Channel] sits relative to the radio's channel assignment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelAssignment {
+    /// Assigned to the radio's channel number given.
+    Assigned(u8),
+    /// Not currently assigned to a radio channel.
+    UnAssigned(),
+}
+
+/// A single ANT channel's message handling, owned by a [crate::router::Router].
+pub trait Channel {
+    /// Record the radio channel number this [Channel] has been assigned (or unassigned from).
+    fn set_channel(&mut self, assignment: ChannelAssignment);
+
+    /// Handle an inbound message addressed to this channel.
+    fn receive_message(&mut self, msg: &AntMessage);
+
+    /// Handle a reassembled burst transfer payload addressed to this channel.
+    ///
+    /// Defaults to a no-op so implementors that don't use burst transfers don't need to know
+    /// about it.
+    fn receive_burst(&mut self, _channel: u8, _data: &[u8]) {}
+
+    /// Pop the next outbound message queued for this channel, if any.
+    fn send_message(&mut self) -> Option<Box<dyn TransmitableMessage>>;
+}
@@ -0,0 +1,22 @@
+// Copyright 2023 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::drivers::DriverError;
+use crate::messages::{AntMessage, TransmitableMessage};
+
+/// Async counterpart to [crate::drivers::Driver] for embassy-style cooperative executors.
+///
+/// Implementations should suspend the task rather than spin while waiting for the underlying
+/// transport to become ready, so [crate::router::Router] can share an executor with other tasks.
+pub trait AsyncDriver<E> {
+    /// Await the next parsed message from the radio.
+    async fn get_message(&mut self) -> Result<AntMessage, DriverError<E>>;
+
+    /// Send a message to the radio, awaiting until the transport accepts it.
+    async fn send_message(&mut self, msg: &dyn TransmitableMessage) -> Result<(), DriverError<E>>;
+}
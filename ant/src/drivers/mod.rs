@@ -9,8 +9,16 @@
 mod serial;
 #[cfg(feature = "usb")]
 mod usb;
+#[cfg(feature = "async")]
+mod asynch;
+#[cfg(feature = "embedded-hal")]
+mod embedded;
 
 pub use serial::*;
 #[cfg(feature = "usb")]
 pub use usb::*;
+#[cfg(feature = "async")]
+pub use asynch::*;
+#[cfg(feature = "embedded-hal")]
+pub use embedded::*;
 // pub use monitor::*;
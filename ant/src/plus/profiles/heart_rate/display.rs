@@ -24,29 +24,80 @@ use crate::plus::NETWORK_RF_FREQUENCY;
 use packed_struct::prelude::{packed_bits::Bits, Integer};
 use packed_struct::{PackedStruct, PrimitiveEnum};
 
-use std::time::Duration;
+use core::time::Duration;
 
+#[cfg(feature = "std")]
 use thingbuf::mpsc::{Receiver, Sender};
 
-pub struct Display {
+#[cfg(not(feature = "std"))]
+use heapless::spsc::{Consumer, Producer};
+
+/// Outbound queue endpoint for a profile.
+///
+/// `std` builds hand a profile a `thingbuf` mpsc [Sender]; `no_std` builds hand it a `heapless`
+/// SPSC [Producer], so the profile layer itself never depends on an allocator.
+pub trait MessageSink<T> {
+    /// Enqueue a message, returning it back if the queue is full or the peer has gone away.
+    fn send(&mut self, msg: T) -> Result<(), T>;
+}
+
+/// Inbound queue endpoint for a profile, see [MessageSink].
+pub trait MessageSource<T> {
+    /// Pop the next queued message without blocking.
+    fn try_recv(&mut self) -> Option<T>;
+}
+
+#[cfg(feature = "std")]
+impl<T> MessageSink<T> for Sender<T> {
+    fn send(&mut self, msg: T) -> Result<(), T> {
+        Sender::try_send(self, msg).map_err(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> MessageSource<T> for Receiver<T> {
+    fn try_recv(&mut self) -> Option<T> {
+        Receiver::try_recv(self).ok()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<'a, T, const N: usize> MessageSink<T> for Producer<'a, T, N> {
+    fn send(&mut self, msg: T) -> Result<(), T> {
+        self.enqueue(msg)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<'a, T, const N: usize> MessageSource<T> for Consumer<'a, T, N> {
+    fn try_recv(&mut self) -> Option<T> {
+        self.dequeue()
+    }
+}
+
+pub struct Display<Tx, Rx> {
     msg_handler: MessageHandler,
     rx_message_callback: Option<fn(&AntMessage)>,
     rx_datapage_callback: Option<fn(Result<MonitorTxDataPage, Error>)>,
     tx_message_callback: Option<fn() -> Option<TxMessageChannelConfig>>,
     tx_datapage_callback: Option<fn() -> Option<TxMessageData>>,
-    tx: Sender<TxMessage>,
-    rx: Receiver<AntMessage>,
+    tx: Tx,
+    rx: Rx,
 }
 
-impl Display {
+impl<Tx, Rx> Display<Tx, Rx>
+where
+    Tx: MessageSink<TxMessage>,
+    Rx: MessageSource<AntMessage>,
+{
     pub fn new(
         // TODO make this a type
         device: Option<(u16, Integer<u8, Bits<4>>)>,
         ant_plus_key_index: u8,
         channel: u8,
         period: Period,
-        tx: Sender<TxMessage>,
-        rx: Receiver<AntMessage>,
+        tx: Tx,
+        rx: Rx,
     ) -> Self {
         let (device_number, transmission_type_extension) = device.unwrap_or((0, 0.into()));
         let channel_config = ChannelConfig {
@@ -165,7 +216,7 @@ impl Display {
     }
 
     pub fn process(&mut self) {
-        while let Ok(msg) = self.rx.try_recv() {
+        while let Some(msg) = self.rx.try_recv() {
             if let Some(f) = self.rx_message_callback {
                 f(&msg);
             }
@@ -186,13 +237,13 @@ impl Display {
 
         // TODO handle errors
         if let Some(msg) = self.msg_handler.send_message() {
-            _ = self.tx.send(msg);
+            let _ = self.tx.send(msg);
             return;
         }
         if let Some(callback) = self.tx_message_callback {
             if let Some(mut msg) = callback() {
                 msg.set_channel(self.msg_handler.get_channel());
-                _ = self.tx.send(msg.into());
+                let _ = self.tx.send(msg.into());
                 return;
             }
         }
@@ -201,7 +252,7 @@ impl Display {
                 if let Some(mut msg) = callback() {
                     msg.set_channel(self.msg_handler.get_channel());
                     self.msg_handler.tx_sent();
-                    _ = self.tx.send(msg.into());
+                    let _ = self.tx.send(msg.into());
                     return;
                 }
             }
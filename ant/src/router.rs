@@ -7,12 +7,20 @@
 // except according to those terms.
 
 use crate::channel::{Channel, ChannelAssignment};
-use crate::drivers::{Driver, DriverError};
-use crate::messages::config::UnAssignChannel;
+#[cfg(feature = "async")]
+use crate::drivers::AsyncDriver;
+#[cfg(feature = "blocking")]
+use crate::drivers::Driver;
+use crate::drivers::DriverError;
+use crate::messages::config::{
+    EncryptionMode, SetCryptoId, SetCryptoInfo, SetCryptoKey, UnAssignChannel,
+};
 use crate::messages::control::{CloseChannel, RequestMessage, RequestableMessageId, ResetSystem};
 use crate::messages::requested_response::Capabilities;
 use crate::messages::{AntMessage, RxMessage, TransmitableMessage};
 
+use packed_struct::PrimitiveEnum;
+
 use std::cell::{Cell, RefCell};
 use std::marker::PhantomData;
 
@@ -21,7 +29,28 @@ use alloc::rc::Rc;
 #[cfg(feature = "std")]
 use std::rc::Rc;
 
-#[derive(Debug)]
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(feature = "async")]
+use core::future::Future;
+
+// The blocking and async impl blocks below both define inherent methods of the same name
+// (`new`, `send`, `process`, ...) on `Router<E, D>`. Cargo features are additive, so enabling
+// both at once would make `D: Driver<E> + AsyncDriver<E>` satisfy both impls and rustc would
+// reject the duplicate inherent method definitions (E0592). The two APIs are meant to be used
+// one at a time; enforce that rather than letting a feature union produce a confusing build
+// error somewhere else in the dependency graph.
+#[cfg(all(feature = "blocking", feature = "async"))]
+compile_error!(
+    "the \"blocking\" and \"async\" features are mutually exclusive: Router defines \
+     identically-named inherent methods for each and enabling both causes duplicate method \
+     definitions (E0592). Pick one."
+);
+
+#[derive(Debug, PartialEq, Eq)]
 pub enum RouterError {
     OutOfChannels(),
     ChannelAlreadyAssigned(),
@@ -29,6 +58,10 @@ pub enum RouterError {
     ChannelOutOfBounds(),
     ChannelNotAssociated(),
     FailedToGetCapabilities(),
+    RequestTimeout(),
+    BurstSequenceError(),
+    EncryptionUnsupported(),
+    EncryptionNotConfigured(),
 }
 
 // This in theory is infinite, but its what the current hardware limit is.
@@ -37,12 +70,59 @@ pub const MAX_CHANNELS: usize = 15;
 
 type SharedChannel = Rc<RefCell<dyn Channel>>;
 
-pub struct Router<E, D: Driver<E>> {
+/// Where a burst frame falls within its transfer, derived from the rolling sequence number and
+/// the final-frame flag carried in `channel_sequence`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PayloadStatus {
+    First,
+    Middle,
+    Last,
+}
+
+impl PayloadStatus {
+    fn new(sequence: u8, last_frame: bool) -> Self {
+        // Sequence 0 always (re)starts a transfer, even if it is also flagged as the last frame
+        // (a single-frame burst) -- check it before `last_frame` so that case still resets the
+        // buffer instead of falling into the continuation arm with nothing buffered yet.
+        if sequence == 0 {
+            PayloadStatus::First
+        } else if last_frame {
+            PayloadStatus::Last
+        } else {
+            PayloadStatus::Middle
+        }
+    }
+}
+
+/// In-flight reassembly state for one channel's burst transfer.
+#[derive(Default)]
+struct BurstBuffer {
+    data: Vec<u8>,
+    // Rolling sequence number of the last accepted frame, None if no burst is in progress.
+    sequence: Option<u8>,
+}
+
+/// Encrypted-channel key/ID/mode negotiated with the radio.
+///
+/// Set via [Router::set_encryption_config] and persisted across `reset(restore=true)` so an
+/// encrypted channel reopened after a reset doesn't have to renegotiate.
+#[derive(Debug, Clone, Copy)]
+pub struct EncryptionConfig {
+    pub key: [u8; 16],
+    pub encryption_id: [u8; 4],
+    pub mode: EncryptionMode,
+}
+
+pub struct Router<E, D> {
     channels: [Option<SharedChannel>; MAX_CHANNELS],
     max_channels: Cell<usize>, // what the hardware reports as some have less than max
     driver: D,
     reset_restore: Cell<bool>,
     rx_message_callback: Option<fn(&AntMessage)>,
+    burst_buffers: RefCell<[BurstBuffer; MAX_CHANNELS]>,
+    encryption: Cell<Option<EncryptionConfig>>,
+    encryption_supported: Cell<bool>,
+    channel_encryption: Cell<[bool; MAX_CHANNELS]>,
     _marker: PhantomData<E>,
 }
 
@@ -55,41 +135,9 @@ impl<E> From<DriverError<E>> for RouterError {
 
 const ROUTER_CAPABILITIES_RETRIES: u8 = 25;
 
-impl<E, D: Driver<E>> Router<E, D> {
-    pub fn new(mut driver: D) -> Result<Self, RouterError> {
-        // Reset system so we are coherent
-        driver.send_message(&ResetSystem::new())?;
-        // Purge driver state
-        while driver.get_message().unwrap_or(None).is_some() {}
-        // When we do first message fetch this should be the first message in the queue
-        driver.send_message(&RequestMessage::new(
-            0,
-            RequestableMessageId::Capabilities,
-            None,
-        ))?;
-        let mut router = Self {
-            channels: [
-                None, None, None, None, None, None, None, None, None, None, None, None, None, None,
-                None,
-            ],
-            max_channels: Cell::new(0),
-            reset_restore: Cell::new(false),
-            driver,
-            rx_message_callback: None,
-            _marker: PhantomData,
-        };
-        // If we don't get a response within 25ms give up
-        let mut i = 0;
-        while router.max_channels.get() == 0 && i < ROUTER_CAPABILITIES_RETRIES {
-            router.process()?;
-            i += 1;
-        }
-        if i == ROUTER_CAPABILITIES_RETRIES {
-            return Err(RouterError::FailedToGetCapabilities());
-        }
-        Ok(router)
-    }
-
+/// Driver-agnostic bookkeeping shared by the blocking and async routers: channel table
+/// management and message/burst routing. Neither depends on how `D` talks to the radio.
+impl<E, D> Router<E, D> {
     /// Add a channel at next available index
     pub fn add_channel(&mut self, channel: SharedChannel) -> Result<(), RouterError> {
         let index = self.channels.iter().position(|x| x.is_none());
@@ -123,54 +171,20 @@ impl<E, D: Driver<E>> Router<E, D> {
         Ok(())
     }
 
-    /// Reboot radio via reset message
-    /// If `restore` is false: dissociate all channels and reset the hardware, router stays associated to
-    /// the driver, if true restore system state.
-    ///
-    /// If you think the radio is not responding it is best to [Router::release] the driver and issue a
-    /// reset via a hardware mechanism then rebuild.
-    pub fn reset(&mut self, restore: bool) -> Result<(), DriverError<E>> {
-        self.driver.send_message(&ResetSystem::new())?;
-        self.reset_restore.set(restore);
-        if !restore {
-            // TODO release profiles
-        }
-        Ok(())
-    }
-
-    /// Transmit a message to the radio
-    pub fn send(&mut self, msg: &dyn TransmitableMessage) -> Result<(), RouterError> {
-        self.driver.send_message(msg)?;
-        Ok(())
-    }
-
-    // TODO add a send and get response
-    //
-    // Logically since this is single threaded, if we send and recieve in the same call, all
-    // messages that may come inbetween send and recieve have no consequence on the code flow. The
-    // only challenge will be handling ownership since we will likely be holding the sender in a
-    // mutable state and if they recieve another message it will be a problem
-
-    /// Given a reference channel remove it from the router
-    // TODO test
-    pub fn remove_channel(&mut self, channel: &SharedChannel) -> Result<(), RouterError> {
+    /// Given a reference channel remove it from the router's bookkeeping. Does not notify the
+    /// radio; callers on a live router should close/unassign the channel first.
+    fn take_channel(&mut self, channel: &SharedChannel) -> Result<usize, RouterError> {
         let index = self
             .channels
             .iter()
             .flatten()
             .position(|x| std::ptr::eq(x, channel));
-        if let Some(x) = index {
-            let chan = self.channels[x].take();
-            if let Some(chan) = chan {
-                chan.borrow_mut()
-                    .set_channel(ChannelAssignment::UnAssigned());
-            }
-            // TODO maybe reset channel?
-            self.driver.send_message(&CloseChannel::new(x as u8))?;
-            self.driver.send_message(&UnAssignChannel::new(x as u8))?;
-            return Ok(());
+        let index = index.ok_or(RouterError::ChannelNotAssociated())?;
+        if let Some(chan) = self.channels[index].take() {
+            chan.borrow_mut()
+                .set_channel(ChannelAssignment::UnAssigned());
         }
-        Err(RouterError::ChannelNotAssociated())
+        Ok(index)
     }
 
     /// Register a callback to obersve all messages, this is meant for debugging or
@@ -191,6 +205,83 @@ impl<E, D: Driver<E>> Router<E, D> {
         Ok(())
     }
 
+    /// Accumulate one 8-byte burst frame, delivering the reassembled transfer to the channel via
+    /// [Channel::receive_burst] once the final frame arrives.
+    ///
+    /// Returns [RouterError::BurstSequenceError] if a frame was dropped or arrived out of order;
+    /// the partial buffer for that channel is discarded so the next sequence-0 frame starts a
+    /// fresh transfer.
+    fn handle_burst_frame(
+        &self,
+        channel: u8,
+        sequence: u8,
+        last_frame: bool,
+        payload: &[u8; 8],
+    ) -> Result<(), RouterError> {
+        if channel as usize >= MAX_CHANNELS {
+            return Err(RouterError::ChannelOutOfBounds());
+        }
+        let status = PayloadStatus::new(sequence, last_frame);
+        let assembled = {
+            let mut buffers = self.burst_buffers.borrow_mut();
+            let buffer = &mut buffers[channel as usize];
+            match status {
+                PayloadStatus::First => {
+                    buffer.data.clear();
+                    buffer.data.extend_from_slice(payload);
+                    buffer.sequence = Some(sequence);
+                }
+                PayloadStatus::Middle | PayloadStatus::Last => {
+                    if buffer.sequence.map(Self::next_burst_sequence) != Some(sequence) {
+                        buffer.data.clear();
+                        buffer.sequence = None;
+                        return Err(RouterError::BurstSequenceError());
+                    }
+                    buffer.data.extend_from_slice(payload);
+                    buffer.sequence = Some(sequence);
+                }
+            }
+            // A single-frame burst is both `First` and last-flagged, so deliver on `last_frame`
+            // directly rather than on `status == Last`.
+            if !last_frame {
+                return Ok(());
+            }
+            buffer.sequence = None;
+            core::mem::take(&mut buffer.data)
+        };
+        self.route_burst(channel, &assembled)
+    }
+
+    /// Next expected rolling burst sequence number: 0 (first frame) then 1, 2, 3, 1, 2, 3, ...
+    fn next_burst_sequence(current: u8) -> u8 {
+        if current == 0 || current == 3 {
+            1
+        } else {
+            current + 1
+        }
+    }
+
+    /// A single dropped or out-of-order burst frame shouldn't abort the whole `process()`/
+    /// `request()` loop and starve every other channel that cycle, so swallow just that error;
+    /// every other [RouterError] (e.g. an out-of-bounds channel) still propagates.
+    fn ignore_burst_sequence_error(result: Result<(), RouterError>) -> Result<(), RouterError> {
+        match result {
+            Err(RouterError::BurstSequenceError()) => Ok(()),
+            other => other,
+        }
+    }
+
+    /// Route a reassembled burst payload to its channel via [Channel::receive_burst].
+    fn route_burst(&self, channel: u8, data: &[u8]) -> Result<(), RouterError> {
+        match &self.channels[channel as usize] {
+            Some(handler) => {
+                handler.borrow_mut().receive_burst(channel, data);
+                Ok(())
+            }
+            None => Err(RouterError::ChannelNotAssociated()),
+        }
+    }
+
     fn broadcast_message(&self, msg: &AntMessage) {
         self.channels
             .iter()
@@ -201,6 +292,47 @@ impl<E, D: Driver<E>> Router<E, D> {
     fn parse_capabilities(&self, msg: &Capabilities) {
         self.max_channels
             .set(msg.base_capabilities.max_ant_channels as usize);
+        self.encryption_supported
+            .set(msg.advanced_capabilities.encrypted_channel_support);
+    }
+
+    /// Is a channel currently configured to use encryption?
+    pub fn is_channel_encrypted(&self, channel: u8) -> bool {
+        self.channel_encryption
+            .get()
+            .get(channel as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Mark a channel as using (or no longer using) the negotiated encryption config.
+    ///
+    /// This only tracks router-side bookkeeping; callers are still responsible for configuring
+    /// the channel itself (e.g. via extended assign channel flags) to match.
+    ///
+    /// Fails with [RouterError::EncryptionUnsupported] if the radio doesn't implement the
+    /// encrypted-channel feature, or [RouterError::EncryptionNotConfigured] if it does but
+    /// [Router::set_encryption_config] hasn't been called yet.
+    pub fn set_channel_encryption(
+        &mut self,
+        channel: u8,
+        enabled: bool,
+    ) -> Result<(), RouterError> {
+        if channel as usize >= MAX_CHANNELS {
+            return Err(RouterError::ChannelOutOfBounds());
+        }
+        if enabled {
+            if !self.encryption_supported.get() {
+                return Err(RouterError::EncryptionUnsupported());
+            }
+            if self.encryption.get().is_none() {
+                return Err(RouterError::EncryptionNotConfigured());
+            }
+        }
+        let mut flags = self.channel_encryption.get();
+        flags[channel as usize] = enabled;
+        self.channel_encryption.set(flags);
+        Ok(())
     }
 
     fn handle_message(&self, msg: &AntMessage) -> Result<(), RouterError> {
@@ -214,10 +346,20 @@ impl<E, D: Driver<E>> Router<E, D> {
                 self.route_message(data.payload.channel_number, msg)
             }
             RxMessage::BurstTransferData(data) => {
-                self.route_message(data.payload.channel_sequence.channel_number.into(), msg)
+                Self::ignore_burst_sequence_error(self.handle_burst_frame(
+                    data.payload.channel_sequence.channel_number.into(),
+                    data.payload.channel_sequence.sequence_number.into(),
+                    data.payload.channel_sequence.last_message,
+                    &data.payload.data,
+                ))
             }
             RxMessage::AdvancedBurstData(data) => {
-                self.route_message(data.channel_sequence.channel_number.into(), msg)
+                Self::ignore_burst_sequence_error(self.handle_burst_frame(
+                    data.channel_sequence.channel_number.into(),
+                    data.channel_sequence.sequence_number.into(),
+                    data.channel_sequence.last_message,
+                    &data.data,
+                ))
             }
             RxMessage::ChannelEvent(data) => self.route_message(data.payload.channel_number, msg),
             RxMessage::ChannelResponse(data) => self.route_message(data.channel_number, msg),
@@ -259,6 +401,181 @@ impl<E, D: Driver<E>> Router<E, D> {
         Ok(())
     }
 
+    /// Does `msg` satisfy a [Router::request] waiting on `expect`?
+    fn is_requested_reply(msg: &RxMessage, expect: RequestableMessageId) -> bool {
+        match msg {
+            RxMessage::Capabilities(_) => expect == RequestableMessageId::Capabilities,
+            RxMessage::ChannelId(_) => expect == RequestableMessageId::ChannelId,
+            RxMessage::ChannelStatus(_) => expect == RequestableMessageId::ChannelStatus,
+            RxMessage::AntVersion(_) => expect == RequestableMessageId::AntVersion,
+            RxMessage::SerialNumber(_) => expect == RequestableMessageId::SerialNumber,
+            RxMessage::EventBufferConfiguration(_) => {
+                expect == RequestableMessageId::EventBufferConfiguration
+            }
+            RxMessage::AdvancedBurstCapabilities(_) => {
+                expect == RequestableMessageId::AdvancedBurstCapabilities
+            }
+            RxMessage::AdvancedBurstCurrentConfiguration(_) => {
+                expect == RequestableMessageId::AdvancedBurstCurrentConfiguration
+            }
+            RxMessage::EncryptionModeParameters(_) => {
+                expect == RequestableMessageId::EncryptionModeParameters
+            }
+            RxMessage::UserNvm(_) => expect == RequestableMessageId::UserNvm,
+            // A ChannelResponse carries the message ID of the command it correlates to; this only
+            // matches when that ID happens to coincide with a RequestableMessageId discriminant,
+            // so it doesn't generally correlate arbitrary command acknowledgements (see the
+            // caveat on Router::request's doc comment).
+            RxMessage::ChannelResponse(data) => data.message_id == expect.to_primitive(),
+            _ => false,
+        }
+    }
+
+    /// Teardown router and return driver
+    pub fn release(self) -> D {
+        self.driver
+    }
+}
+
+/// Blocking router surface for the synchronous [Driver] trait. Disable the `blocking` feature
+/// and enable `async` to run the router on an embassy-style cooperative executor instead.
+#[cfg(feature = "blocking")]
+impl<E, D: Driver<E>> Router<E, D> {
+    pub fn new(mut driver: D) -> Result<Self, RouterError> {
+        // Reset system so we are coherent
+        driver.send_message(&ResetSystem::new())?;
+        // Purge driver state
+        while driver.get_message().unwrap_or(None).is_some() {}
+        // When we do first message fetch this should be the first message in the queue
+        driver.send_message(&RequestMessage::new(
+            0,
+            RequestableMessageId::Capabilities,
+            None,
+        ))?;
+        let mut router = Self {
+            channels: [
+                None, None, None, None, None, None, None, None, None, None, None, None, None, None,
+                None,
+            ],
+            max_channels: Cell::new(0),
+            reset_restore: Cell::new(false),
+            driver,
+            rx_message_callback: None,
+            burst_buffers: RefCell::new(Default::default()),
+            encryption: Cell::new(None),
+            encryption_supported: Cell::new(false),
+            channel_encryption: Cell::new([false; MAX_CHANNELS]),
+            _marker: PhantomData,
+        };
+        // If we don't get a response within ROUTER_CAPABILITIES_RETRIES polls give up
+        let mut i = 0;
+        while router.max_channels.get() == 0 && i < ROUTER_CAPABILITIES_RETRIES {
+            router.process()?;
+            i += 1;
+        }
+        if router.max_channels.get() == 0 {
+            return Err(RouterError::FailedToGetCapabilities());
+        }
+        // encryption_supported is set as a side effect of parsing the Capabilities reply above,
+        // so there is no separate probe to wait on here.
+        Ok(router)
+    }
+
+    /// Reboot radio via reset message
+    /// If `restore` is false: dissociate all channels and reset the hardware, router stays associated to
+    /// the driver, if true restore system state.
+    ///
+    /// If you think the radio is not responding it is best to [Router::release] the driver and issue a
+    /// reset via a hardware mechanism then rebuild.
+    pub fn reset(&mut self, restore: bool) -> Result<(), DriverError<E>> {
+        self.driver.send_message(&ResetSystem::new())?;
+        self.reset_restore.set(restore);
+        if restore {
+            if let Some(config) = self.encryption.get() {
+                self.driver.send_message(&SetCryptoKey::new(config.key))?;
+                self.driver
+                    .send_message(&SetCryptoId::new(config.encryption_id))?;
+                self.driver.send_message(&SetCryptoInfo::new(config.mode))?;
+            }
+        } else {
+            // TODO release profiles
+        }
+        Ok(())
+    }
+
+    /// Configure the encryption key/ID/mode used for encrypted channels.
+    ///
+    /// Persisted across `reset(restore=true)`. Fails with [RouterError::EncryptionUnsupported]
+    /// if the radio's parsed capabilities didn't advertise the encrypted-channel feature.
+    pub fn set_encryption_config(&mut self, config: EncryptionConfig) -> Result<(), RouterError> {
+        if !self.encryption_supported.get() {
+            return Err(RouterError::EncryptionUnsupported());
+        }
+        self.driver.send_message(&SetCryptoKey::new(config.key))?;
+        self.driver
+            .send_message(&SetCryptoId::new(config.encryption_id))?;
+        self.driver.send_message(&SetCryptoInfo::new(config.mode))?;
+        self.encryption.set(Some(config));
+        Ok(())
+    }
+
+    /// Transmit a message to the radio
+    pub fn send(&mut self, msg: &dyn TransmitableMessage) -> Result<(), RouterError> {
+        self.driver.send_message(msg)?;
+        Ok(())
+    }
+
+    /// Send a message and block until a reply matching `expect` is received.
+    ///
+    /// This is the synchronous counterpart to [Router::send]: it sends `msg`, then pumps the
+    /// driver for up to `retries` iterations of [Router::process]'s inner loop, looking for a
+    /// message whose type matches `expect` (e.g. [RequestableMessageId::Capabilities] is
+    /// satisfied by a [RxMessage::Capabilities]). A [RxMessage::ChannelResponse] also satisfies
+    /// `expect` when its raw message ID equals `expect`'s -- this is only reachable for commands
+    /// that happen to share a discriminant with a [RequestableMessageId] variant; correlating an
+    /// arbitrary command's acknowledgement (e.g. for `AcknowledgedData`, which isn't itself
+    /// requestable) isn't expressible through this `expect` type. Every message, including the
+    /// matching reply itself, is routed through [Router::handle_message] before being returned,
+    /// so channel state stays coherent and other channels' outbound queues keep draining while we
+    /// wait.
+    ///
+    /// Returns [RouterError::RequestTimeout] if no matching reply arrives within the retry
+    /// budget.
+    pub fn request(
+        &mut self,
+        msg: &dyn TransmitableMessage,
+        expect: RequestableMessageId,
+        retries: u16,
+    ) -> Result<RxMessage, RouterError> {
+        self.driver.send_message(msg)?;
+        for _ in 0..retries {
+            while let Some(ant_msg) = self.driver.get_message()? {
+                let is_match = Self::is_requested_reply(&ant_msg.message, expect);
+                self.handle_message(&ant_msg)?;
+                if is_match {
+                    return Ok(ant_msg.message);
+                }
+            }
+            let driver = &mut self.driver;
+            self.channels
+                .iter()
+                .flatten()
+                .try_for_each(|x| Self::send_channel(driver, x))?;
+        }
+        Err(RouterError::RequestTimeout())
+    }
+
+    /// Given a reference channel remove it from the router
+    // TODO test
+    pub fn remove_channel(&mut self, channel: &SharedChannel) -> Result<(), RouterError> {
+        let index = self.take_channel(channel)?;
+        // TODO maybe reset channel?
+        self.driver.send_message(&CloseChannel::new(index as u8))?;
+        self.driver
+            .send_message(&UnAssignChannel::new(index as u8))?;
+        Ok(())
+    }
+
     /// Parse all incoming messages and run callbacks
     pub fn process(&mut self) -> Result<(), RouterError> {
         while let Some(msg) = self.driver.get_message()? {
@@ -271,15 +588,295 @@ impl<E, D: Driver<E>> Router<E, D> {
             .try_for_each(|x| Self::send_channel(driver, x))
     }
 
-    /// Teardown router and return driver
-    pub fn release(self) -> D {
+    fn send_channel(driver: &mut D, channel: &SharedChannel) -> Result<(), RouterError> {
+        while let Some(msg) = channel.borrow_mut().send_message() {
+            driver.send_message(&msg)?;
+        }
+        Ok(())
+    }
+}
+
+/// Async router surface for embassy-style cooperative executors. Built on [AsyncDriver] instead
+/// of [Driver]: every wait point is an `.await`, so a single-threaded executor can run other
+/// tasks while the router waits on the radio.
+#[cfg(feature = "async")]
+impl<E, D: AsyncDriver<E>> Router<E, D> {
+    /// Build the router, waiting for the post-reset `Capabilities` reply.
+    ///
+    /// `timeout` is raced against that wait: if it resolves first, `new` fails with
+    /// [RouterError::FailedToGetCapabilities] instead of hanging forever on a device that never
+    /// replies. Pass a future driven by your executor's timer, e.g.
+    /// `embassy_time::Timer::after(Duration::from_secs(1))`; pass `core::future::pending()` to
+    /// wait for the reply indefinitely.
+    pub async fn new(
+        mut driver: D,
+        timeout: impl Future<Output = ()>,
+    ) -> Result<Self, RouterError> {
+        driver.send_message(&ResetSystem::new()).await?;
+        driver
+            .send_message(&RequestMessage::new(
+                0,
+                RequestableMessageId::Capabilities,
+                None,
+            ))
+            .await?;
+        let mut router = Self {
+            channels: [
+                None, None, None, None, None, None, None, None, None, None, None, None, None, None,
+                None,
+            ],
+            max_channels: Cell::new(0),
+            reset_restore: Cell::new(false),
+            driver,
+            rx_message_callback: None,
+            burst_buffers: RefCell::new(Default::default()),
+            encryption: Cell::new(None),
+            encryption_supported: Cell::new(false),
+            channel_encryption: Cell::new([false; MAX_CHANNELS]),
+            _marker: PhantomData,
+        };
+        let got_capabilities = Self::with_timeout(
+            async {
+                while router.max_channels.get() == 0 {
+                    router.process().await?;
+                }
+                Ok::<(), RouterError>(())
+            },
+            timeout,
+        )
+        .await;
+        match got_capabilities {
+            Some(result) => result.map(|()| router),
+            None => Err(RouterError::FailedToGetCapabilities()),
+        }
+    }
+
+    /// Race `fut` against `timeout`, returning `None` if `timeout` resolves first.
+    async fn with_timeout<F: Future>(
+        fut: F,
+        timeout: impl Future<Output = ()>,
+    ) -> Option<F::Output> {
+        let mut fut = core::pin::pin!(fut);
+        let mut timeout = core::pin::pin!(timeout);
+        core::future::poll_fn(|cx| {
+            if let core::task::Poll::Ready(v) = fut.as_mut().poll(cx) {
+                return core::task::Poll::Ready(Some(v));
+            }
+            if timeout.as_mut().poll(cx).is_ready() {
+                return core::task::Poll::Ready(None);
+            }
+            core::task::Poll::Pending
+        })
+        .await
+    }
+
+    /// Transmit a message to the radio
+    pub async fn send(&mut self, msg: &dyn TransmitableMessage) -> Result<(), RouterError> {
+        self.driver.send_message(msg).await?;
+        Ok(())
+    }
+
+    /// Configure the encryption key/ID/mode used for encrypted channels.
+    ///
+    /// The config is kept on the router, but this async surface has no `reset`, so nothing
+    /// re-sends it to the radio after the radio resets. Fails with
+    /// [RouterError::EncryptionUnsupported] if the radio's parsed capabilities didn't advertise
+    /// the encrypted-channel feature.
+    pub async fn set_encryption_config(
+        &mut self,
+        config: EncryptionConfig,
+    ) -> Result<(), RouterError> {
+        if !self.encryption_supported.get() {
+            return Err(RouterError::EncryptionUnsupported());
+        }
+        self.driver
+            .send_message(&SetCryptoKey::new(config.key))
+            .await?;
         self.driver
+            .send_message(&SetCryptoId::new(config.encryption_id))
+            .await?;
+        self.driver
+            .send_message(&SetCryptoInfo::new(config.mode))
+            .await?;
+        self.encryption.set(Some(config));
+        Ok(())
     }
 
-    fn send_channel(driver: &mut D, channel: &SharedChannel) -> Result<(), RouterError> {
+    /// Given a reference channel remove it from the router
+    pub async fn remove_channel(&mut self, channel: &SharedChannel) -> Result<(), RouterError> {
+        let index = self.take_channel(channel)?;
+        self.driver
+            .send_message(&CloseChannel::new(index as u8))
+            .await?;
+        self.driver
+            .send_message(&UnAssignChannel::new(index as u8))
+            .await?;
+        Ok(())
+    }
+
+    /// Await and parse the next incoming message, then drain each channel's outbound queue.
+    pub async fn process(&mut self) -> Result<(), RouterError> {
+        let msg = self.driver.get_message().await?;
+        self.handle_message(&msg)?;
+        let driver = &mut self.driver;
+        for channel in self.channels.iter().flatten() {
+            Self::send_channel(driver, channel).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_channel(driver: &mut D, channel: &SharedChannel) -> Result<(), RouterError> {
         while let Some(msg) = channel.borrow_mut().send_message() {
-            driver.send_message(&msg)?;
+            driver.send_message(&msg).await?;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    use std::boxed::Box;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::boxed::Box;
+
+    #[derive(Default)]
+    struct FakeChannel {
+        bursts: Vec<Vec<u8>>,
+    }
+
+    impl Channel for FakeChannel {
+        fn set_channel(&mut self, _assignment: ChannelAssignment) {}
+        fn receive_message(&mut self, _msg: &AntMessage) {}
+        fn receive_burst(&mut self, _channel: u8, data: &[u8]) {
+            self.bursts.push(data.to_vec());
+        }
+        fn send_message(&mut self) -> Option<Box<dyn TransmitableMessage>> {
+            None
+        }
+    }
+
+    // A bare-bones Router with a single FakeChannel at index 0, for driving handle_burst_frame
+    // directly without a real Driver.
+    fn test_router() -> (Router<(), ()>, Rc<RefCell<FakeChannel>>) {
+        let fake = Rc::new(RefCell::new(FakeChannel::default()));
+        let handler: SharedChannel = fake.clone();
+        let mut router = Router::<(), ()> {
+            channels: Default::default(),
+            max_channels: Cell::new(MAX_CHANNELS),
+            driver: (),
+            reset_restore: Cell::new(false),
+            rx_message_callback: None,
+            burst_buffers: RefCell::new(Default::default()),
+            encryption: Cell::new(None),
+            encryption_supported: Cell::new(false),
+            channel_encryption: Cell::new([false; MAX_CHANNELS]),
+            _marker: PhantomData,
+        };
+        router.channels[0] = Some(handler);
+        (router, fake)
+    }
+
+    #[test]
+    fn handle_burst_frame_single_frame_delivers_immediately() {
+        let (router, fake) = test_router();
+        assert!(router
+            .handle_burst_frame(0, 0, true, &[1, 2, 3, 4, 5, 6, 7, 8])
+            .is_ok());
+        assert_eq!(fake.borrow().bursts, vec![vec![1, 2, 3, 4, 5, 6, 7, 8]]);
+    }
+
+    #[test]
+    fn handle_burst_frame_multi_frame_accumulates_then_delivers() {
+        let (router, fake) = test_router();
+        assert!(router
+            .handle_burst_frame(0, 0, false, &[1, 1, 1, 1, 1, 1, 1, 1])
+            .is_ok());
+        assert!(fake.borrow().bursts.is_empty());
+        assert!(router
+            .handle_burst_frame(0, 1, false, &[2, 2, 2, 2, 2, 2, 2, 2])
+            .is_ok());
+        assert!(fake.borrow().bursts.is_empty());
+        assert!(router
+            .handle_burst_frame(0, 2, true, &[3, 3, 3, 3, 3, 3, 3, 3])
+            .is_ok());
+        assert_eq!(
+            fake.borrow().bursts,
+            vec![[[1u8; 8], [2u8; 8], [3u8; 8]].concat()]
+        );
+    }
+
+    #[test]
+    fn handle_burst_frame_sequence_0_resets_mid_burst() {
+        let (router, fake) = test_router();
+        assert!(router
+            .handle_burst_frame(0, 0, false, &[1, 1, 1, 1, 1, 1, 1, 1])
+            .is_ok());
+        // A fresh sequence-0 frame mid-transfer restarts the buffer instead of erroring.
+        assert!(router
+            .handle_burst_frame(0, 0, true, &[9, 9, 9, 9, 9, 9, 9, 9])
+            .is_ok());
+        assert_eq!(fake.borrow().bursts, vec![vec![9, 9, 9, 9, 9, 9, 9, 9]]);
+    }
+
+    #[test]
+    fn handle_burst_frame_dropped_frame_errors_and_clears_buffer() {
+        let (router, fake) = test_router();
+        assert!(router
+            .handle_burst_frame(0, 0, false, &[1, 1, 1, 1, 1, 1, 1, 1])
+            .is_ok());
+        // Sequence 2 arrives instead of the expected 1: out of order.
+        assert_eq!(
+            router.handle_burst_frame(0, 2, true, &[3, 3, 3, 3, 3, 3, 3, 3]),
+            Err(RouterError::BurstSequenceError())
+        );
+        assert!(fake.borrow().bursts.is_empty());
+        // The buffer was cleared, so a new sequence-0 frame starts a clean transfer.
+        assert!(router
+            .handle_burst_frame(0, 0, true, &[4, 4, 4, 4, 4, 4, 4, 4])
+            .is_ok());
+        assert_eq!(fake.borrow().bursts, vec![vec![4, 4, 4, 4, 4, 4, 4, 4]]);
+    }
+
+    #[test]
+    fn payload_status_first_frame() {
+        assert_eq!(PayloadStatus::new(0, false), PayloadStatus::First);
+    }
+
+    #[test]
+    fn payload_status_single_frame_burst_is_first() {
+        // A one-frame burst is flagged last on its only (sequence-0) frame; treated as First so
+        // the reassembly buffer (re)starts, then handle_burst_frame delivers it immediately
+        // because last_frame is set.
+        assert_eq!(PayloadStatus::new(0, true), PayloadStatus::First);
+    }
+
+    #[test]
+    fn payload_status_middle_and_last_frames() {
+        assert_eq!(PayloadStatus::new(1, false), PayloadStatus::Middle);
+        assert_eq!(PayloadStatus::new(2, true), PayloadStatus::Last);
+    }
+
+    #[test]
+    fn next_burst_sequence_wraps_after_three() {
+        assert_eq!(Router::<(), ()>::next_burst_sequence(0), 1);
+        assert_eq!(Router::<(), ()>::next_burst_sequence(1), 2);
+        assert_eq!(Router::<(), ()>::next_burst_sequence(2), 3);
+        assert_eq!(Router::<(), ()>::next_burst_sequence(3), 1);
+    }
+
+    #[test]
+    fn ignore_burst_sequence_error_swallows_only_that_variant() {
+        assert!(Router::<(), ()>::ignore_burst_sequence_error(Err(
+            RouterError::BurstSequenceError()
+        ))
+        .is_ok());
+        assert!(matches!(
+            Router::<(), ()>::ignore_burst_sequence_error(Err(RouterError::ChannelOutOfBounds())),
+            Err(RouterError::ChannelOutOfBounds())
+        ));
+    }
+}